@@ -17,15 +17,25 @@ mod decl {
     use crossbeam_utils::atomic::AtomicCell;
     use num_bigint::BigInt;
     use num_traits::{One, Signed, ToPrimitive, Zero};
+    use std::collections::{HashMap, VecDeque};
     use std::fmt;
 
+    // `pos` and `cached_iter` always advance together, so both live behind a
+    // single lock: iterating the same `chain` from multiple threads is safe
+    // (no torn reads / double-advances), though the interleaving of results
+    // across threads is unspecified, matching CPython's documented guarantee.
+    #[derive(Debug)]
+    struct ChainState {
+        pos: usize,
+        cached_iter: Option<PyObjectRef>,
+    }
+
     #[pyattr]
     #[pyclass(name = "chain")]
     #[derive(Debug, PyValue)]
     struct PyItertoolsChain {
         iterables: Vec<PyObjectRef>,
-        cur_idx: AtomicCell<usize>,
-        cached_iter: PyRwLock<Option<PyObjectRef>>,
+        state: PyMutex<ChainState>,
     }
 
     #[pyimpl(with(PyIter))]
@@ -34,8 +44,10 @@ mod decl {
         fn tp_new(cls: PyTypeRef, args: FuncArgs, vm: &VirtualMachine) -> PyResult {
             PyItertoolsChain {
                 iterables: args.args,
-                cur_idx: AtomicCell::new(0),
-                cached_iter: PyRwLock::new(None),
+                state: PyMutex::new(ChainState {
+                    pos: 0,
+                    cached_iter: None,
+                }),
             }
             .into_pyresult_with_type(vm, cls)
         }
@@ -48,8 +60,10 @@ mod decl {
         ) -> PyResult<PyRef<Self>> {
             PyItertoolsChain {
                 iterables: vm.extract_elements(&iterable)?,
-                cur_idx: AtomicCell::new(0),
-                cached_iter: PyRwLock::new(None),
+                state: PyMutex::new(ChainState {
+                    pos: 0,
+                    cached_iter: None,
+                }),
             }
             .into_ref_with_type(vm, cls)
         }
@@ -57,37 +71,64 @@ mod decl {
     impl PyIter for PyItertoolsChain {
         fn next(zelf: &PyRef<Self>, vm: &VirtualMachine) -> PyResult {
             loop {
-                let pos = zelf.cur_idx.load();
-                if pos >= zelf.iterables.len() {
-                    break;
+                let mut state = zelf.state.lock();
+                if state.pos >= zelf.iterables.len() {
+                    return Err(vm.new_stop_iteration());
                 }
-                let cur_iter = if zelf.cached_iter.read().is_none() {
-                    // We need to call "get_iter" outside of the lock.
-                    let iter = get_iter(vm, zelf.iterables[pos].clone())?;
-                    *zelf.cached_iter.write() = Some(iter.clone());
-                    iter
-                } else if let Some(cached_iter) = (*zelf.cached_iter.read()).clone() {
-                    cached_iter
-                } else {
-                    // Someone changed cached iter to None since we checked.
-                    continue;
+
+                let cur_iter = match state.cached_iter.clone() {
+                    Some(cur_iter) => cur_iter,
+                    None => {
+                        // We need to call "get_iter" outside of the lock.
+                        let pos = state.pos;
+                        drop(state);
+                        let iter = get_iter(vm, zelf.iterables[pos].clone())?;
+
+                        state = zelf.state.lock();
+                        if state.pos != pos {
+                            // Someone already advanced past this position
+                            // while we were fetching our own iterator for it;
+                            // start this next() call over.
+                            drop(state);
+                            continue;
+                        }
+                        match state.cached_iter.clone() {
+                            // Another thread already installed an iterator
+                            // for this position first; use that one instead
+                            // of overwriting it, which would otherwise drop
+                            // its progress and double-advance the source.
+                            Some(existing) => existing,
+                            None => {
+                                state.cached_iter = Some(iter.clone());
+                                iter
+                            }
+                        }
+                    }
                 };
+                drop(state);
 
                 // We need to call "call_next" outside of the lock.
                 match call_next(vm, &cur_iter) {
                     Ok(ok) => return Ok(ok),
                     Err(err) => {
                         if err.isinstance(&vm.ctx.exceptions.stop_iteration) {
-                            zelf.cur_idx.fetch_add(1);
-                            *zelf.cached_iter.write() = None;
+                            let mut state = zelf.state.lock();
+                            // Only advance if this is still the cached iterator;
+                            // otherwise another thread already advanced past it.
+                            if state
+                                .cached_iter
+                                .as_ref()
+                                .map_or(false, |cached| cached.is(&cur_iter))
+                            {
+                                state.pos += 1;
+                                state.cached_iter = None;
+                            }
                         } else {
                             return Err(err);
                         }
                     }
                 }
             }
-
-            Err(vm.new_stop_iteration())
         }
     }
 
@@ -196,13 +237,20 @@ mod decl {
         }
     }
 
+    // `saved` and `index` always advance together, so both live behind a
+    // single lock, same thread-safety contract as `chain` above.
+    #[derive(Debug)]
+    struct CycleState {
+        saved: Vec<PyObjectRef>,
+        index: usize,
+    }
+
     #[pyattr]
     #[pyclass(name = "cycle")]
     #[derive(Debug, PyValue)]
     struct PyItertoolsCycle {
         iter: PyObjectRef,
-        saved: PyRwLock<Vec<PyObjectRef>>,
-        index: AtomicCell<usize>,
+        state: PyMutex<CycleState>,
     }
 
     impl SlotConstructor for PyItertoolsCycle {
@@ -213,8 +261,10 @@ mod decl {
 
             PyItertoolsCycle {
                 iter,
-                saved: PyRwLock::new(Vec::new()),
-                index: AtomicCell::new(0),
+                state: PyMutex::new(CycleState {
+                    saved: Vec::new(),
+                    index: 0,
+                }),
             }
             .into_pyresult_with_type(vm, cls)
         }
@@ -224,22 +274,25 @@ mod decl {
     impl PyItertoolsCycle {}
     impl PyIter for PyItertoolsCycle {
         fn next(zelf: &PyRef<Self>, vm: &VirtualMachine) -> PyResult {
-            let item = if let Some(item) = get_next_object(vm, &zelf.iter)? {
-                zelf.saved.write().push(item.clone());
+            // We need to call the wrapped iterator outside of the lock.
+            let item = get_next_object(vm, &zelf.iter)?;
+
+            let mut state = zelf.state.lock();
+            let item = if let Some(item) = item {
+                state.saved.push(item.clone());
                 item
             } else {
-                let saved = zelf.saved.read();
-                if saved.len() == 0 {
+                if state.saved.is_empty() {
                     return Err(vm.new_stop_iteration());
                 }
 
-                let last_index = zelf.index.fetch_add(1);
-
-                if last_index >= saved.len() - 1 {
-                    zelf.index.store(0);
-                }
-
-                saved[last_index].clone()
+                let index = state.index;
+                state.index = if index >= state.saved.len() - 1 {
+                    0
+                } else {
+                    index + 1
+                };
+                state.saved[index].clone()
             };
 
             Ok(item)
@@ -526,6 +579,10 @@ mod decl {
         }
     }
 
+    // All mutable state (current key/value, pending-group flag, active
+    // grouper) lives behind the single `state` lock below, giving `groupby`
+    // and `_grouper` the same atomic-step thread-safety contract as `chain`
+    // and `cycle`.
     #[pyattr]
     #[pyclass(name = "groupby")]
     #[derive(PyValue)]
@@ -586,13 +643,18 @@ mod decl {
     }
     impl PyIter for PyItertoolsGroupBy {
         fn next(zelf: &PyRef<Self>, vm: &VirtualMachine) -> PyResult {
+            // Unlike `chain`, where re-fetching `get_iter` on the same
+            // iterable is cheap and idempotent (so a dropped lock can be
+            // re-validated after the fact), `advance` pulls the next element
+            // straight out of the single shared `iterable`: calling it twice
+            // loses an element. So the lock is held for the whole step here
+            // instead of being dropped around the call, making each `next`
+            // step on a `groupby` atomic rather than racily re-checked.
             let mut state = zelf.state.lock();
             state.grouper = None;
 
             if !state.next_group {
-                // FIXME: unnecessary clone. current_key always exist until assigning new
                 let current_key = state.current_key.clone();
-                drop(state);
 
                 let (value, key) = if let Some(old_key) = current_key {
                     loop {
@@ -605,7 +667,6 @@ mod decl {
                     zelf.advance(vm)?
                 };
 
-                state = zelf.state.lock();
                 state.current_value = Some(value);
                 state.current_key = Some(key);
             }
@@ -635,25 +696,25 @@ mod decl {
     impl PyItertoolsGrouper {}
     impl PyIter for PyItertoolsGrouper {
         fn next(zelf: &PyRef<Self>, vm: &VirtualMachine) -> PyResult {
-            let old_key = {
-                let mut state = zelf.groupby.state.lock();
+            // Held for the whole step, same reasoning as PyItertoolsGroupBy::next:
+            // `advance` is not idempotent, so the lock can't be dropped and
+            // re-validated after the fact without risking a double-advance.
+            let mut state = zelf.groupby.state.lock();
 
-                if !state.is_current(zelf) {
-                    return Err(vm.new_stop_iteration());
-                }
+            if !state.is_current(zelf) {
+                return Err(vm.new_stop_iteration());
+            }
 
-                // check to see if the value has already been retrieved from the iterator
-                if let Some(val) = state.current_value.take() {
-                    return Ok(val);
-                }
+            // check to see if the value has already been retrieved from the iterator
+            if let Some(val) = state.current_value.take() {
+                return Ok(val);
+            }
 
-                state.current_key.as_ref().unwrap().clone()
-            };
+            let old_key = state.current_key.as_ref().unwrap().clone();
             let (value, key) = zelf.groupby.advance(vm)?;
             if vm.bool_eq(&key, &old_key)? {
                 Ok(value)
             } else {
-                let mut state = zelf.groupby.state.lock();
                 state.current_value = Some(value);
                 state.current_key = Some(key);
                 state.next_group = true;
@@ -1516,4 +1577,636 @@ mod decl {
             Ok(vm.ctx.new_tuple(vec![old, new]))
         }
     }
+
+    #[pyattr]
+    #[pyclass(name = "window")]
+    #[derive(Debug, PyValue)]
+    struct PyItertoolsWindow {
+        iterator: PyObjectRef,
+        n: usize,
+        buffer: PyRwLock<VecDeque<PyObjectRef>>,
+    }
+
+    #[derive(FromArgs)]
+    struct WindowNewArgs {
+        #[pyarg(positional)]
+        iterable: PyObjectRef,
+        #[pyarg(positional)]
+        n: usize,
+    }
+
+    impl SlotConstructor for PyItertoolsWindow {
+        type Args = WindowNewArgs;
+
+        fn py_new(
+            cls: PyTypeRef,
+            Self::Args { iterable, n }: Self::Args,
+            vm: &VirtualMachine,
+        ) -> PyResult {
+            if n == 0 {
+                return Err(vm.new_value_error("n must be a positive integer".to_owned()));
+            }
+
+            let iterator = get_iter(vm, iterable)?;
+
+            PyItertoolsWindow {
+                iterator,
+                n,
+                buffer: PyRwLock::new(VecDeque::with_capacity(n)),
+            }
+            .into_pyresult_with_type(vm, cls)
+        }
+    }
+
+    #[pyimpl(with(PyIter, SlotConstructor))]
+    impl PyItertoolsWindow {}
+    impl PyIter for PyItertoolsWindow {
+        fn next(zelf: &PyRef<Self>, vm: &VirtualMachine) -> PyResult {
+            let mut buffer = zelf.buffer.write();
+            while buffer.len() < zelf.n {
+                buffer.push_back(call_next(vm, &zelf.iterator)?);
+            }
+            let res = vm.ctx.new_tuple(buffer.iter().cloned().collect());
+            buffer.pop_front();
+            Ok(res)
+        }
+    }
+
+    #[pyattr]
+    #[pyclass(name = "intersperse")]
+    #[derive(Debug, PyValue)]
+    struct PyItertoolsIntersperse {
+        iterator: PyObjectRef,
+        separator: PyObjectRef,
+        peek: PyRwLock<Option<PyObjectRef>>,
+        emit_separator: AtomicCell<bool>,
+    }
+
+    #[derive(FromArgs)]
+    struct IntersperseNewArgs {
+        #[pyarg(positional)]
+        separator: PyObjectRef,
+        #[pyarg(positional)]
+        iterable: PyObjectRef,
+    }
+
+    impl SlotConstructor for PyItertoolsIntersperse {
+        type Args = IntersperseNewArgs;
+
+        fn py_new(
+            cls: PyTypeRef,
+            Self::Args {
+                separator,
+                iterable,
+            }: Self::Args,
+            vm: &VirtualMachine,
+        ) -> PyResult {
+            let iterator = get_iter(vm, iterable)?;
+
+            PyItertoolsIntersperse {
+                iterator,
+                separator,
+                peek: PyRwLock::new(None),
+                emit_separator: AtomicCell::new(false),
+            }
+            .into_pyresult_with_type(vm, cls)
+        }
+    }
+
+    #[pyimpl(with(PyIter, SlotConstructor))]
+    impl PyItertoolsIntersperse {}
+    impl PyIter for PyItertoolsIntersperse {
+        fn next(zelf: &PyRef<Self>, vm: &VirtualMachine) -> PyResult {
+            if zelf.emit_separator.load() {
+                zelf.emit_separator.store(false);
+                return Ok(zelf.separator.clone());
+            }
+
+            let item = match zelf.peek.write().take() {
+                Some(item) => item,
+                None => call_next(vm, &zelf.iterator)?,
+            };
+
+            // Peek one element ahead so a trailing separator is never emitted
+            // after the last element.
+            match call_next(vm, &zelf.iterator) {
+                Ok(next_item) => {
+                    *zelf.peek.write() = Some(next_item);
+                    zelf.emit_separator.store(true);
+                }
+                Err(err) => {
+                    if !err.isinstance(&vm.ctx.exceptions.stop_iteration) {
+                        return Err(err);
+                    }
+                }
+            }
+
+            Ok(item)
+        }
+    }
+
+    fn obj_lt(vm: &VirtualMachine, a: &PyObjectRef, b: &PyObjectRef) -> PyResult<bool> {
+        // A plain `__lt__` call can return `NotImplemented` (truthy!) for
+        // unorderable/reflected-only operands instead of raising, which would
+        // silently mis-order the heap. `bool_lt` is the rich-comparison
+        // helper (sibling of `bool_eq` used in `groupby` above) that honors
+        // reflection and turns an unorderable pair into a real `TypeError`.
+        vm.bool_lt(a, b)
+    }
+
+    fn heap_sift_up(heap: &mut [PyObjectRef], mut i: usize, vm: &VirtualMachine) -> PyResult<()> {
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if obj_lt(vm, &heap[parent], &heap[i])? {
+                heap.swap(parent, i);
+                i = parent;
+            } else {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn heap_sift_down(heap: &mut [PyObjectRef], mut i: usize, vm: &VirtualMachine) -> PyResult<()> {
+        let n = heap.len();
+        loop {
+            let left = 2 * i + 1;
+            let right = 2 * i + 2;
+            let mut largest = i;
+            if left < n && obj_lt(vm, &heap[largest], &heap[left])? {
+                largest = left;
+            }
+            if right < n && obj_lt(vm, &heap[largest], &heap[right])? {
+                largest = right;
+            }
+            if largest == i {
+                break;
+            }
+            heap.swap(i, largest);
+            i = largest;
+        }
+        Ok(())
+    }
+
+    #[pyattr]
+    #[pyclass(name = "k_smallest")]
+    #[derive(Debug, PyValue)]
+    struct PyItertoolsKSmallest {
+        iterable: PyObjectRef,
+        k: usize,
+        result: PyRwLock<Option<std::vec::IntoIter<PyObjectRef>>>,
+    }
+
+    #[derive(FromArgs)]
+    struct KSmallestNewArgs {
+        #[pyarg(positional)]
+        iterable: PyObjectRef,
+        #[pyarg(positional)]
+        k: usize,
+    }
+
+    impl SlotConstructor for PyItertoolsKSmallest {
+        type Args = KSmallestNewArgs;
+
+        fn py_new(
+            cls: PyTypeRef,
+            Self::Args { iterable, k }: Self::Args,
+            vm: &VirtualMachine,
+        ) -> PyResult {
+            PyItertoolsKSmallest {
+                iterable,
+                k,
+                result: PyRwLock::new(None),
+            }
+            .into_pyresult_with_type(vm, cls)
+        }
+    }
+
+    #[pyimpl(with(PyIter, SlotConstructor))]
+    impl PyItertoolsKSmallest {
+        // Keep at most `k` elements in a bounded max-heap so only a single
+        // pass (O(n log k)) over the source is needed instead of sorting it:
+        // every incoming element is pushed, and the heap sheds its current
+        // maximum whenever it grows past capacity `k`.
+        fn compute(&self, vm: &VirtualMachine) -> PyResult<Vec<PyObjectRef>> {
+            let mut heap: Vec<PyObjectRef> = Vec::new();
+            let iter = get_iter(vm, self.iterable.clone())?;
+            while let Some(item) = get_next_object(vm, &iter)? {
+                if self.k == 0 {
+                    continue;
+                }
+                heap.push(item);
+                let last = heap.len() - 1;
+                heap_sift_up(&mut heap, last, vm)?;
+
+                if heap.len() > self.k {
+                    let last = heap.len() - 1;
+                    heap.swap(0, last);
+                    heap.pop();
+                    heap_sift_down(&mut heap, 0, vm)?;
+                }
+            }
+
+            // Drain the heap by repeatedly popping its current maximum, then
+            // reverse to turn the descending pops into ascending order.
+            let mut descending = Vec::with_capacity(heap.len());
+            while !heap.is_empty() {
+                let last = heap.len() - 1;
+                heap.swap(0, last);
+                descending.push(heap.pop().unwrap());
+                if !heap.is_empty() {
+                    heap_sift_down(&mut heap, 0, vm)?;
+                }
+            }
+            descending.reverse();
+            Ok(descending)
+        }
+    }
+    impl PyIter for PyItertoolsKSmallest {
+        fn next(zelf: &PyRef<Self>, vm: &VirtualMachine) -> PyResult {
+            let mut result = zelf.result.write();
+            if result.is_none() {
+                *result = Some(zelf.compute(vm)?.into_iter());
+            }
+            result
+                .as_mut()
+                .unwrap()
+                .next()
+                .ok_or_else(|| vm.new_stop_iteration())
+        }
+    }
+
+    // Drives the right-to-left index scan inline instead of chaining over a
+    // sequence of `PyItertoolsCombinations` objects (one per size `r`): it
+    // reuses the same scan `PyItertoolsCombinations::next` uses, without the
+    // overhead of constructing and discarding n + 1 intermediate iterator
+    // objects. This is the only powerset implementation in the module.
+    #[pyattr]
+    #[pyclass(name = "powerset")]
+    #[derive(Debug, PyValue)]
+    struct PyItertoolsPowerset {
+        pool: Vec<PyObjectRef>,
+        r: AtomicCell<usize>,
+        indices: PyRwLock<Vec<usize>>,
+    }
+
+    impl SlotConstructor for PyItertoolsPowerset {
+        type Args = PyObjectRef;
+
+        fn py_new(cls: PyTypeRef, iterable: Self::Args, vm: &VirtualMachine) -> PyResult {
+            let pool = vm.extract_elements(&iterable)?;
+
+            PyItertoolsPowerset {
+                pool,
+                r: AtomicCell::new(0),
+                indices: PyRwLock::new(Vec::new()),
+            }
+            .into_pyresult_with_type(vm, cls)
+        }
+    }
+
+    #[pyimpl(with(PyIter, SlotConstructor))]
+    impl PyItertoolsPowerset {}
+    impl PyIter for PyItertoolsPowerset {
+        fn next(zelf: &PyRef<Self>, vm: &VirtualMachine) -> PyResult {
+            let n = zelf.pool.len();
+            let r = zelf.r.load();
+            if r > n {
+                return Err(vm.new_stop_iteration());
+            }
+
+            let res = vm.ctx.new_tuple(
+                zelf.indices
+                    .read()
+                    .iter()
+                    .map(|&i| zelf.pool[i].clone())
+                    .collect(),
+            );
+
+            let mut indices = zelf.indices.write();
+
+            if r == 0 {
+                // The lone empty tuple at r == 0 has no index scan of its own;
+                // move straight on to the 1-element combinations.
+                zelf.r.store(1);
+                *indices = (0..1).collect();
+                return Ok(res);
+            }
+
+            // Right-to-left index scan, identical to PyItertoolsCombinations::next.
+            let mut idx = r as isize - 1;
+            while idx >= 0 && indices[idx as usize] == idx as usize + n - r {
+                idx -= 1;
+            }
+
+            if idx < 0 {
+                // Combinations of size r are exhausted; move on to size r + 1.
+                let next_r = r + 1;
+                zelf.r.store(next_r);
+                *indices = (0..next_r).collect();
+            } else {
+                indices[idx as usize] += 1;
+                for j in idx as usize + 1..r {
+                    indices[j] = indices[j - 1] + 1;
+                }
+            }
+
+            Ok(res)
+        }
+    }
+
+    #[derive(FromArgs)]
+    struct UniqueArgs {
+        iterable: PyObjectRef,
+        #[pyarg(any, optional)]
+        key: OptionalOption<PyObjectRef>,
+    }
+
+    #[pyattr]
+    #[pyclass(name = "unique_everseen")]
+    #[derive(Debug, PyValue)]
+    struct PyItertoolsUniqueEverseen {
+        iterator: PyObjectRef,
+        key_func: Option<PyObjectRef>,
+        // Keys that hash cleanly are bucketed by hash value (still compared
+        // with `bool_eq` within a bucket to cope with hash collisions);
+        // anything whose `__hash__` fails falls back to a linear scan, the
+        // same strategy CPython's pure-Python `unique_everseen` recipe uses.
+        seen_hashable: PyRwLock<HashMap<i64, Vec<PyObjectRef>>>,
+        seen_unhashable: PyRwLock<Vec<PyObjectRef>>,
+    }
+
+    impl SlotConstructor for PyItertoolsUniqueEverseen {
+        type Args = UniqueArgs;
+
+        fn py_new(cls: PyTypeRef, args: Self::Args, vm: &VirtualMachine) -> PyResult {
+            let iterator = get_iter(vm, args.iterable)?;
+
+            PyItertoolsUniqueEverseen {
+                iterator,
+                key_func: args.key.flatten(),
+                seen_hashable: PyRwLock::new(HashMap::new()),
+                seen_unhashable: PyRwLock::new(Vec::new()),
+            }
+            .into_pyresult_with_type(vm, cls)
+        }
+    }
+
+    fn key_hash(key: &PyObjectRef, vm: &VirtualMachine) -> PyResult<i64> {
+        let hash_obj = vm.call_method(key, "__hash__", ())?;
+        let hash_int = hash_obj
+            .payload::<PyInt>()
+            .ok_or_else(|| vm.new_type_error("__hash__ must return int".to_owned()))?;
+        Ok(hash_int.as_bigint().to_i64().unwrap_or(0))
+    }
+
+    #[pyimpl(with(PyIter, SlotConstructor))]
+    impl PyItertoolsUniqueEverseen {}
+    impl PyIter for PyItertoolsUniqueEverseen {
+        fn next(zelf: &PyRef<Self>, vm: &VirtualMachine) -> PyResult {
+            loop {
+                let value = call_next(vm, &zelf.iterator)?;
+                let key = match &zelf.key_func {
+                    Some(f) => vm.invoke(f, (value.clone(),))?,
+                    None => value.clone(),
+                };
+
+                let is_new = match key_hash(&key, vm) {
+                    Ok(hash) => {
+                        let mut seen = zelf.seen_hashable.write();
+                        let bucket = seen.entry(hash).or_insert_with(Vec::new);
+                        let mut found = false;
+                        for existing in bucket.iter() {
+                            if vm.bool_eq(existing, &key)? {
+                                found = true;
+                                break;
+                            }
+                        }
+                        if !found {
+                            bucket.push(key.clone());
+                        }
+                        !found
+                    }
+                    Err(err) => {
+                        // Only unhashable keys (TypeError) fall back to the
+                        // linear scan; any other failure must propagate.
+                        if !err.isinstance(&vm.ctx.exceptions.type_error) {
+                            return Err(err);
+                        }
+
+                        let mut seen = zelf.seen_unhashable.write();
+                        let mut found = false;
+                        for existing in seen.iter() {
+                            if vm.bool_eq(existing, &key)? {
+                                found = true;
+                                break;
+                            }
+                        }
+                        if !found {
+                            seen.push(key.clone());
+                        }
+                        !found
+                    }
+                };
+
+                if is_new {
+                    return Ok(value);
+                }
+            }
+        }
+    }
+
+    #[pyattr]
+    #[pyclass(name = "unique_justseen")]
+    #[derive(Debug, PyValue)]
+    struct PyItertoolsUniqueJustseen {
+        iterator: PyObjectRef,
+        key_func: Option<PyObjectRef>,
+        last_key: PyRwLock<Option<PyObjectRef>>,
+    }
+
+    impl SlotConstructor for PyItertoolsUniqueJustseen {
+        type Args = UniqueArgs;
+
+        fn py_new(cls: PyTypeRef, args: Self::Args, vm: &VirtualMachine) -> PyResult {
+            let iterator = get_iter(vm, args.iterable)?;
+
+            PyItertoolsUniqueJustseen {
+                iterator,
+                key_func: args.key.flatten(),
+                last_key: PyRwLock::new(None),
+            }
+            .into_pyresult_with_type(vm, cls)
+        }
+    }
+
+    #[pyimpl(with(PyIter, SlotConstructor))]
+    impl PyItertoolsUniqueJustseen {}
+    impl PyIter for PyItertoolsUniqueJustseen {
+        fn next(zelf: &PyRef<Self>, vm: &VirtualMachine) -> PyResult {
+            loop {
+                let value = call_next(vm, &zelf.iterator)?;
+                let key = match &zelf.key_func {
+                    Some(f) => vm.invoke(f, (value.clone(),))?,
+                    None => value.clone(),
+                };
+
+                let mut last_key = zelf.last_key.write();
+                let is_duplicate = match &*last_key {
+                    Some(prev) => vm.bool_eq(prev, &key)?,
+                    None => false,
+                };
+                *last_key = Some(key);
+                if !is_duplicate {
+                    return Ok(value);
+                }
+            }
+        }
+    }
+
+    // `tree_reduce` and `tree_fold1` both combine elements in a balanced
+    // binary-tree shape rather than left-to-right, but via different
+    // algorithms (halving-rounds over a materialized Vec here vs. an
+    // incremental level-stack below) and are kept as separate functions
+    // rather than collapsed into one. Both take `(function, iterable)` for
+    // consistency with each other and with the rest of this module (e.g.
+    // `starmap(function, iterable)`).
+    #[pyfunction]
+    fn tree_reduce(function: PyObjectRef, iterable: PyObjectRef, vm: &VirtualMachine) -> PyResult {
+        let mut values: Vec<PyObjectRef> = vm.extract_elements(&iterable)?;
+        if values.is_empty() {
+            return Err(vm.new_type_error("tree_reduce() of empty iterable".to_owned()));
+        }
+
+        // Halve the vector each round, combining adjacent pairs, so `function`
+        // is applied in a tree of depth log2(n) instead of a length-n chain.
+        while values.len() > 1 {
+            let mut next_round = Vec::with_capacity((values.len() + 1) / 2);
+            let mut iter = values.into_iter();
+            while let Some(first) = iter.next() {
+                match iter.next() {
+                    Some(second) => next_round.push(vm.invoke(&function, (first, second))?),
+                    None => next_round.push(first),
+                }
+            }
+            values = next_round;
+        }
+
+        Ok(values.into_iter().next().unwrap())
+    }
+
+    #[pyfunction]
+    fn tree_fold1(function: PyObjectRef, iterable: PyObjectRef, vm: &VirtualMachine) -> PyResult {
+        let iter = get_iter(vm, iterable)?;
+        // Stack of (level, value) pairs: equal-level neighbours collapse as
+        // soon as they meet, so the final tree has depth ~log2(n).
+        let mut stack: Vec<(u32, PyObjectRef)> = Vec::new();
+
+        while let Some(item) = get_next_object(vm, &iter)? {
+            let mut level = 0u32;
+            let mut value = item;
+            while let Some(&(top_level, _)) = stack.last() {
+                if top_level != level {
+                    break;
+                }
+                let (_, popped_value) = stack.pop().unwrap();
+                value = vm.invoke(&function, (popped_value, value))?;
+                level += 1;
+            }
+            stack.push((level, value));
+        }
+
+        let mut stack = stack.into_iter();
+        let (_, mut acc) = stack
+            .next()
+            .ok_or_else(|| vm.new_value_error("tree_fold1() of empty sequence".to_owned()))?;
+        for (_, value) in stack {
+            acc = vm.invoke(&function, (acc, value))?;
+        }
+        Ok(acc)
+    }
+
+    #[pyattr]
+    #[pyclass(name = "coalesce")]
+    #[derive(Debug, PyValue)]
+    struct PyItertoolsCoalesce {
+        iterator: PyObjectRef,
+        func: PyObjectRef,
+        // Like PyItertoolsPairwise::old, the pending accumulator is carried
+        // across `next()` calls in a lock rather than as local state.
+        pending: PyRwLock<Option<PyObjectRef>>,
+        exhausted: AtomicCell<bool>,
+    }
+
+    #[derive(FromArgs)]
+    struct CoalesceNewArgs {
+        #[pyarg(positional)]
+        iterable: PyObjectRef,
+        #[pyarg(positional)]
+        func: PyObjectRef,
+    }
+
+    impl SlotConstructor for PyItertoolsCoalesce {
+        type Args = CoalesceNewArgs;
+
+        fn py_new(
+            cls: PyTypeRef,
+            Self::Args { iterable, func }: Self::Args,
+            vm: &VirtualMachine,
+        ) -> PyResult {
+            let iterator = get_iter(vm, iterable)?;
+
+            PyItertoolsCoalesce {
+                iterator,
+                func,
+                pending: PyRwLock::new(None),
+                exhausted: AtomicCell::new(false),
+            }
+            .into_pyresult_with_type(vm, cls)
+        }
+    }
+
+    #[pyimpl(with(PyIter, SlotConstructor))]
+    impl PyItertoolsCoalesce {}
+    impl PyIter for PyItertoolsCoalesce {
+        fn next(zelf: &PyRef<Self>, vm: &VirtualMachine) -> PyResult {
+            if zelf.exhausted.load() {
+                return Err(vm.new_stop_iteration());
+            }
+
+            let mut accum = match zelf.pending.write().take() {
+                Some(accum) => accum,
+                None => call_next(vm, &zelf.iterator)?,
+            };
+
+            loop {
+                match call_next(vm, &zelf.iterator) {
+                    Ok(item) => {
+                        let merged = vm.invoke(&zelf.func, (accum.clone(), item))?;
+                        let pair = vm.extract_elements(&merged)?;
+                        if pair.len() != 2 {
+                            return Err(vm.new_value_error(
+                                "coalesce() func must return a 2-tuple (merged, value)".to_owned(),
+                            ));
+                        }
+                        let merged_flag = pair[0].clone().try_to_bool(vm)?;
+                        let value = pair[1].clone();
+                        if merged_flag {
+                            accum = value;
+                        } else {
+                            *zelf.pending.write() = Some(value);
+                            return Ok(accum);
+                        }
+                    }
+                    Err(err) => {
+                        if !err.isinstance(&vm.ctx.exceptions.stop_iteration) {
+                            return Err(err);
+                        }
+                        zelf.exhausted.store(true);
+                        return Ok(accum);
+                    }
+                }
+            }
+        }
+    }
 }